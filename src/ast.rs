@@ -8,6 +8,7 @@ pub enum UnaryOperator {
 pub enum BinaryOperator {
     Slash,
     Star,
+    Percent,
     Plus,
     Minus,
     Greater,
@@ -16,6 +17,9 @@ pub enum BinaryOperator {
     LessEqual,
     EqualEqual,
     NotEqual,
+    Ampersand,
+    Pipe,
+    Caret,
 }
 
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -24,25 +28,55 @@ pub enum LogicalOperator {
     Or,
 }
 
+/// An expression together with the source span (start/end char offsets) it
+/// was parsed from, so the interpreter can point diagnostics at the exact
+/// offending range instead of just a message.
 #[derive(Debug, PartialEq, Clone)]
-pub enum Expr {
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: (u32, u32),
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: (u32, u32)) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExprKind {
     Binary(Box<Expr>, BinaryOperator, Box<Expr>),
+    Logical(Box<Expr>, LogicalOperator, Box<Expr>),
     Unary(UnaryOperator, Box<Expr>),
+    Call(Box<Expr>, Vec<Expr>),
     Number(f64),
     Boolean(bool),
     Nil,
     String(String),
-    Variable(String),
-    // Assign(var_name, expr_to_assign)
-    Assignment(String, Box<Expr>),
+    // Variable(name, scope_depth) -- scope_depth is filled in by the resolver.
+    Variable(String, Option<usize>),
+    // Assignment(var_name, expr_to_assign, scope_depth)
+    Assignment(String, Box<Expr>, Option<usize>),
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Stmt {
     Block(Vec<Stmt>),
     Expr(Expr),
+    If(Expr, Box<Stmt>, Option<Box<Stmt>>),
+    While(Expr, Box<Stmt>),
+    // For(condition, increment, body) -- a dedicated variant (rather than
+    // desugaring the increment into the body as a trailing `Block` statement)
+    // so `continue` can run the increment before re-testing the condition
+    // instead of skipping it by unwinding straight out of the body block.
+    For(Expr, Option<Expr>, Box<Stmt>),
+    Break,
+    Continue,
     Let(String, Option<Expr>),
     Print(Expr),
+    // Function(name, params, body)
+    Function(String, Vec<String>, Box<Stmt>),
+    Return(Option<Expr>),
 }
 
 pub type Ast = Vec<Stmt>;
@@ -1,10 +1,18 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use crate::value::Value;
 
+// a single block scope, shared (not copied) wherever it's captured so that
+// mutations made through one handle -- e.g. by a closure -- are visible
+// through every other handle to the same scope.
+type Scope = Rc<RefCell<HashMap<String, Option<Value>>>>;
+
+#[derive(Debug, Clone)]
 pub struct Environment {
     // environment for each block scope
-    environments: Vec<HashMap<String, Option<Value>>>,
+    environments: Vec<Scope>,
 }
 
 impl Environment {
@@ -12,12 +20,12 @@ impl Environment {
         Self {
             // predefined scope for Global Scope
             // TODO: make this empty vec by parsing src global scope to block itself.
-            environments: vec![HashMap::new()],
+            environments: vec![Rc::new(RefCell::new(HashMap::new()))],
         }
     }
 
     pub fn begin_scope(&mut self) {
-        self.environments.push(HashMap::new())
+        self.environments.push(Rc::new(RefCell::new(HashMap::new())))
     }
 
     pub fn end_scope(&mut self) {
@@ -25,19 +33,18 @@ impl Environment {
     }
 
     pub fn define_var(&mut self, name: String, value: Option<Value>) {
-        let env = self.environments.last_mut().expect("No scope yet.");
-        env.insert(name, value);
+        let env = self.environments.last().expect("No scope yet.");
+        env.borrow_mut().insert(name, value);
     }
 
     pub fn get_var(&mut self, name: String) -> Result<Value, String> {
         for i in (0..self.environments.len()).rev() {
-            if self.environments[i].contains_key(&name) {
-                let value = &self.environments[i][&name];
-                if let Some(value) = value {
-                    return Ok(value.clone());
-                } else {
-                    return Err("Variable is not initialized.".to_string());
-                }
+            let scope = self.environments[i].borrow();
+            if scope.contains_key(&name) {
+                return match &scope[&name] {
+                    Some(value) => Ok(value.clone()),
+                    None => Err("Variable is not initialized.".to_string()),
+                };
             }
         }
 
@@ -46,12 +53,43 @@ impl Environment {
 
     pub fn assign_var(&mut self, name: String, value: Value) -> Result<Value, String> {
         for i in (0..self.environments.len()).rev() {
-            if self.environments[i].contains_key(&name) {
-                self.environments[i].insert(name.clone(), Some(value));
-                break;
+            if self.environments[i].borrow().contains_key(&name) {
+                self.environments[i].borrow_mut().insert(name.clone(), Some(value.clone()));
+                return Ok(value);
             }
         }
 
         Err(format!("Undefined variable '{}'", name))
     }
+
+    // depth is the number of scopes to hop outward from the innermost one,
+    // as computed by the resolver.
+    fn scope_index(&self, depth: usize) -> usize {
+        self.environments.len() - 1 - depth
+    }
+
+    pub fn get_var_at(&mut self, depth: usize, name: &str) -> Result<Value, String> {
+        let index = self.scope_index(depth);
+        match self.environments[index].borrow().get(name) {
+            Some(Some(value)) => Ok(value.clone()),
+            Some(None) => Err("Variable is not initialized.".to_string()),
+            None => Err("Variable is undefined.".to_string()),
+        }
+    }
+
+    pub fn assign_var_at(
+        &mut self,
+        depth: usize,
+        name: String,
+        value: Value,
+    ) -> Result<Value, String> {
+        let index = self.scope_index(depth);
+        let mut scope = self.environments[index].borrow_mut();
+        if scope.contains_key(&name) {
+            scope.insert(name, Some(value.clone()));
+            return Ok(value);
+        }
+
+        Err(format!("Undefined variable '{}'", name))
+    }
 }
@@ -0,0 +1,82 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    Lex,
+    Parse,
+    Resolve,
+    Runtime,
+}
+
+/// A diagnostic carrying the source span (start/end char offsets) it applies
+/// to, so callers can render a caret-underlined snippet instead of a bare
+/// message. Produced by the parser, resolver, and interpreter alike.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoxError {
+    pub kind: ErrorKind,
+    pub span: (u32, u32),
+    pub message: String,
+}
+
+impl RoxError {
+    pub fn lex(span: (u32, u32), message: impl Into<String>) -> Self {
+        Self {
+            kind: ErrorKind::Lex,
+            span,
+            message: message.into(),
+        }
+    }
+
+    pub fn parse(span: (u32, u32), message: impl Into<String>) -> Self {
+        Self {
+            kind: ErrorKind::Parse,
+            span,
+            message: message.into(),
+        }
+    }
+
+    pub fn resolve(span: (u32, u32), message: impl Into<String>) -> Self {
+        Self {
+            kind: ErrorKind::Resolve,
+            span,
+            message: message.into(),
+        }
+    }
+
+    pub fn runtime(span: (u32, u32), message: impl Into<String>) -> Self {
+        Self {
+            kind: ErrorKind::Runtime,
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// Renders this diagnostic as a line/column header followed by the
+    /// offending source line with a caret underline beneath it.
+    pub fn report(&self, src: &[char]) -> String {
+        let start = (self.span.0 as usize).min(src.len());
+        let line = 1 + src[..start].iter().filter(|&&c| c == '\n').count();
+        let line_start = src[..start]
+            .iter()
+            .rposition(|&c| c == '\n')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let line_end = src[start..]
+            .iter()
+            .position(|&c| c == '\n')
+            .map(|i| start + i)
+            .unwrap_or(src.len());
+        let column = start - line_start + 1;
+        let underline_len = (self.span.1 as usize).saturating_sub(self.span.0 as usize) + 1;
+        let line_text: String = src[line_start..line_end].iter().collect();
+
+        format!(
+            "{:?}Err: {}\n  --> line {}, column {}\n{}\n{}{}\n",
+            self.kind,
+            self.message,
+            line,
+            column,
+            line_text,
+            " ".repeat(column - 1),
+            "^".repeat(underline_len),
+        )
+    }
+}
@@ -1,55 +1,112 @@
-use crate::ast::{BinaryOperator, Expr, LogicalOperator, Stmt, UnaryOperator};
+use crate::ast::{BinaryOperator, Expr, ExprKind, LogicalOperator, Stmt, UnaryOperator};
 use crate::environment::Environment;
-use crate::parser::parse;
+use crate::error::RoxError;
+use crate::parser::{parse_repl, ReplInput};
+use crate::resolver;
+use crate::stdlib;
 use crate::value::Value;
 
 pub struct Interpreter {
     envs: Environment,
 }
 
+/// Propagates either a runtime error or a `return`/`break`/`continue`
+/// unwinding through nested statement execution, distinct from an ordinary
+/// `Err`.
+#[derive(Debug)]
+enum Signal {
+    Error(RoxError),
+    Return(Value),
+    Break,
+    Continue,
+}
+
 impl Interpreter {
     pub fn new() -> Self {
-        Self {
-            envs: Environment::new(),
-        }
+        let mut envs = Environment::new();
+        stdlib::load(&mut envs);
+        Self { envs }
     }
 
-    pub fn interpret(&mut self, src: &str) {
-        let ast = parse(src);
-        for stmt in ast {
-            println!("Stmt => {:?}", stmt); // DEBUG:
-            match self.run(stmt) {
-                Ok(_) => {}
-                Err(err) => println!("RuntimeErr: {}", err),
-            };
+    // Runs `src`, returning the value of a bare REPL expression (e.g. `1 + 2`)
+    // so the caller can print it without requiring an explicit `print`.
+    pub fn interpret(&mut self, src: &str) -> Option<Value> {
+        let chars: Vec<char> = src.chars().collect();
+
+        match parse_repl(src) {
+            ReplInput::Expr(expr) => {
+                let mut stmts = vec![Stmt::Expr(expr)];
+                for err in resolver::resolve(&mut stmts) {
+                    print!("{}", err.report(&chars));
+                }
+                let expr = match stmts.into_iter().next() {
+                    Some(Stmt::Expr(expr)) => expr,
+                    _ => unreachable!(),
+                };
+
+                match self.evaluate(expr) {
+                    Ok(value) => Some(value),
+                    Err(Signal::Error(err)) => {
+                        print!("{}", err.report(&chars));
+                        None
+                    }
+                    Err(Signal::Return(_) | Signal::Break | Signal::Continue) => None,
+                }
+            }
+            ReplInput::Stmts(mut ast) => {
+                for err in resolver::resolve(&mut ast) {
+                    print!("{}", err.report(&chars));
+                }
+                for stmt in ast {
+                    match self.run(stmt) {
+                        Ok(_) => {}
+                        Err(Signal::Error(err)) => print!("{}", err.report(&chars)),
+                        Err(Signal::Return(_)) => {} // return outside of a function, nothing to do.
+                        Err(Signal::Break) | Err(Signal::Continue) => {} // outside of a loop, nothing to do.
+                    };
+                }
+                None
+            }
         }
     }
 
-    fn run(&mut self, stmt: Stmt) -> Result<(), String> {
+    fn run(&mut self, stmt: Stmt) -> Result<(), Signal> {
         match stmt {
             Stmt::Block(stmts) => self.block(stmts),
             Stmt::Expr(expr) => self.expr_stmt(expr),
             Stmt::If(expr, then_stmt, else_stmt) => self.if_stmt(expr, then_stmt, else_stmt),
+            Stmt::While(expr, body) => self.while_stmt(expr, body),
+            Stmt::For(cond, increment, body) => self.for_stmt(cond, increment, body),
+            Stmt::Break => Err(Signal::Break),
+            Stmt::Continue => Err(Signal::Continue),
             Stmt::Print(expr) => self.print_stmt(expr),
             Stmt::Let(i, e) => self.let_stmt(i, e),
+            Stmt::Function(name, params, body) => self.function_decl(name, params, body),
+            Stmt::Return(expr) => self.return_stmt(expr),
         }
     }
 }
 
 /// Statement
 impl Interpreter {
-    fn block(&mut self, stmts: Vec<Stmt>) -> Result<(), String> {
+    fn block(&mut self, stmts: Vec<Stmt>) -> Result<(), Signal> {
         self.envs.begin_scope();
 
-        for stmt in stmts {
-            self.run(stmt)?
-        }
+        // run via a closure so the scope is popped on every exit path, not
+        // just the success path -- a `break`/`continue`/`return`/error
+        // propagating out of a nested statement would otherwise leak it.
+        let result = (|| {
+            for stmt in stmts {
+                self.run(stmt)?
+            }
+            Ok(())
+        })();
 
         self.envs.end_scope();
-        Ok(())
+        result
     }
 
-    fn expr_stmt(&mut self, expr: Expr) -> Result<(), String> {
+    fn expr_stmt(&mut self, expr: Expr) -> Result<(), Signal> {
         self.evaluate(expr)?;
         Ok(())
     }
@@ -59,7 +116,7 @@ impl Interpreter {
         expr: Expr,
         then_stmt: Box<Stmt>,
         else_stmt: Option<Box<Stmt>>,
-    ) -> Result<(), String> {
+    ) -> Result<(), Signal> {
         if self.evaluate(expr)? == Value::Bool(true) {
             self.run(*then_stmt)?;
         } else {
@@ -70,13 +127,50 @@ impl Interpreter {
         Ok(())
     }
 
-    fn print_stmt(&mut self, expr: Expr) -> Result<(), String> {
+    fn while_stmt(&mut self, cond: Expr, body: Box<Stmt>) -> Result<(), Signal> {
+        while self.evaluate(cond.clone())? == Value::Bool(true) {
+            match self.run((*body).clone()) {
+                Ok(()) => {}
+                Err(Signal::Break) => break,
+                Err(Signal::Continue) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    fn for_stmt(
+        &mut self,
+        cond: Expr,
+        increment: Option<Expr>,
+        body: Box<Stmt>,
+    ) -> Result<(), Signal> {
+        while self.evaluate(cond.clone())? == Value::Bool(true) {
+            match self.run((*body).clone()) {
+                Ok(()) => {}
+                Err(Signal::Break) => break,
+                // `continue` jumps straight to the increment, same as the
+                // implicit fall-through below -- unlike `while_stmt`, this
+                // must still run the increment before the next condition
+                // check, or `continue` would skip it forever.
+                Err(Signal::Continue) => {}
+                Err(err) => return Err(err),
+            }
+
+            if let Some(increment) = increment.clone() {
+                self.evaluate(increment)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn print_stmt(&mut self, expr: Expr) -> Result<(), Signal> {
         let value = self.evaluate(expr)?;
         println!("{:?}", value);
         Ok(())
     }
 
-    fn let_stmt(&mut self, name: String, initializer: Option<Expr>) -> Result<(), String> {
+    fn let_stmt(&mut self, name: String, initializer: Option<Expr>) -> Result<(), Signal> {
         let mut value = Value::Nil;
         if let Some(expr) = initializer {
             value = self.evaluate(expr)?;
@@ -85,31 +179,75 @@ impl Interpreter {
         self.envs.define_var(name, Some(value));
         Ok(())
     }
+
+    fn function_decl(
+        &mut self,
+        name: String,
+        params: Vec<String>,
+        body: Box<Stmt>,
+    ) -> Result<(), Signal> {
+        // capture handles to the defining environment's scopes (shared, not
+        // copied) so the function closes over the variables visible at its
+        // declaration site and sees later changes made to them.
+        let function = Value::Function(params, body, self.envs.clone());
+        self.envs.define_var(name, Some(function));
+        Ok(())
+    }
+
+    fn return_stmt(&mut self, expr: Option<Expr>) -> Result<(), Signal> {
+        let value = match expr {
+            Some(expr) => self.evaluate(expr)?,
+            None => Value::Nil,
+        };
+
+        Err(Signal::Return(value))
+    }
 }
 
 /// Expression
 impl Interpreter {
-    fn evaluate(&mut self, expr: Expr) -> Result<Value, String> {
-        match expr {
-            Expr::Binary(l, o, r) => self.binary(l, o, r),
-            Expr::Logical(l, o, r) => self.logical(l, o, r),
-            Expr::Unary(o, expr) => self.unary(o, expr),
-            Expr::Number(number) => Ok(Value::Num(number)),
-            Expr::Boolean(value) => Ok(Value::Bool(value)),
-            Expr::Nil => Ok(Value::Nil),
-            Expr::String(s) => Ok(Value::String(s)),
-            Expr::Variable(var) => self.variable(var), // TODO:
-            Expr::Assignment(name, expr) => self.assignment(name, expr),
+    fn evaluate(&mut self, expr: Expr) -> Result<Value, Signal> {
+        let span = expr.span;
+        match expr.kind {
+            ExprKind::Binary(l, o, r) => self.binary(span, l, o, r),
+            ExprKind::Logical(l, o, r) => self.logical(l, o, r),
+            ExprKind::Unary(o, expr) => self.unary(span, o, expr),
+            ExprKind::Call(callee, args) => self.call(span, *callee, args),
+            ExprKind::Number(number) => Ok(Value::Num(number)),
+            ExprKind::Boolean(value) => Ok(Value::Bool(value)),
+            ExprKind::Nil => Ok(Value::Nil),
+            ExprKind::String(s) => Ok(Value::String(s)),
+            ExprKind::Variable(var, depth) => self.variable(span, var, depth),
+            ExprKind::Assignment(name, expr, depth) => self.assignment(span, name, expr, depth),
         }
     }
 
-    fn assignment(&mut self, name: String, expr: Box<Expr>) -> Result<Value, String> {
+    fn assignment(
+        &mut self,
+        span: (u32, u32),
+        name: String,
+        expr: Box<Expr>,
+        depth: Option<usize>,
+    ) -> Result<Value, Signal> {
         let value = self.evaluate(*expr)?;
-        self.envs.assign_var(name, value)
+        match depth {
+            Some(depth) => self.envs.assign_var_at(depth, name, value),
+            None => self.envs.assign_var(name, value),
+        }
+        .map_err(|msg| Signal::Error(RoxError::runtime(span, msg)))
     }
 
-    fn variable(&mut self, identifier: String) -> Result<Value, String> {
-        self.envs.get_var(identifier)
+    fn variable(
+        &mut self,
+        span: (u32, u32),
+        identifier: String,
+        depth: Option<usize>,
+    ) -> Result<Value, Signal> {
+        match depth {
+            Some(depth) => self.envs.get_var_at(depth, &identifier),
+            None => self.envs.get_var(identifier),
+        }
+        .map_err(|msg| Signal::Error(RoxError::runtime(span, msg)))
     }
 
     fn logical(
@@ -117,7 +255,7 @@ impl Interpreter {
         left: Box<Expr>,
         op: LogicalOperator,
         right: Box<Expr>,
-    ) -> Result<Value, String> {
+    ) -> Result<Value, Signal> {
         let left = self.evaluate(*left)?;
 
         match op {
@@ -138,31 +276,122 @@ impl Interpreter {
 
     fn binary(
         &mut self,
+        span: (u32, u32),
         left: Box<Expr>,
         op: BinaryOperator,
         right: Box<Expr>,
-    ) -> Result<Value, String> {
+    ) -> Result<Value, Signal> {
         let left = self.evaluate(*left)?;
         let right = self.evaluate(*right)?;
+        let err = |msg: String| Signal::Error(RoxError::runtime(span, msg));
+        // comparisons only make sense between numbers; don't fall back to the
+        // derived `Ord` for other types.
+        let numeric = |left: &Value, right: &Value| -> Result<(f64, f64), String> {
+            match (left, right) {
+                (Value::Num(a), Value::Num(b)) => Ok((*a, *b)),
+                _ => Err("Operands must be numbers.".to_string()),
+            }
+        };
         match op {
-            BinaryOperator::Slash => left / right,
-            BinaryOperator::Star => left * right,
-            BinaryOperator::Plus => left + right,
-            BinaryOperator::Minus => left - right,
-            BinaryOperator::Greater => Ok(Value::Bool(left > right)),
-            BinaryOperator::GreaterEqual => Ok(Value::Bool(left >= right)),
-            BinaryOperator::Less => Ok(Value::Bool(left < right)),
-            BinaryOperator::LessEqual => Ok(Value::Bool(left <= right)),
+            BinaryOperator::Slash => (left / right).map_err(err),
+            BinaryOperator::Star => (left * right).map_err(err),
+            BinaryOperator::Percent => (left % right).map_err(err),
+            BinaryOperator::Plus => (left + right).map_err(err),
+            BinaryOperator::Minus => (left - right).map_err(err),
+            BinaryOperator::Greater => numeric(&left, &right).map(|(a, b)| Value::Bool(a > b)).map_err(err),
+            BinaryOperator::GreaterEqual => numeric(&left, &right).map(|(a, b)| Value::Bool(a >= b)).map_err(err),
+            BinaryOperator::Less => numeric(&left, &right).map(|(a, b)| Value::Bool(a < b)).map_err(err),
+            BinaryOperator::LessEqual => numeric(&left, &right).map(|(a, b)| Value::Bool(a <= b)).map_err(err),
             BinaryOperator::EqualEqual => Ok(Value::Bool(left == right)),
             BinaryOperator::NotEqual => Ok(Value::Bool(left != right)),
+            BinaryOperator::Ampersand => (left & right).map_err(err),
+            BinaryOperator::Pipe => (left | right).map_err(err),
+            BinaryOperator::Caret => (left ^ right).map_err(err),
         }
     }
 
-    fn unary(&mut self, op: UnaryOperator, expr: Box<Expr>) -> Result<Value, String> {
+    fn unary(&mut self, span: (u32, u32), op: UnaryOperator, expr: Box<Expr>) -> Result<Value, Signal> {
         let expr = self.evaluate(*expr)?;
+        let err = |msg: String| Signal::Error(RoxError::runtime(span, msg));
         match op {
-            UnaryOperator::Not => !Value::from(expr),
-            UnaryOperator::Minus => !Value::from(expr),
+            UnaryOperator::Not => (!expr).map_err(err),
+            UnaryOperator::Minus => (-expr).map_err(err),
+        }
+    }
+
+    fn call(&mut self, span: (u32, u32), callee: Expr, args: Vec<Expr>) -> Result<Value, Signal> {
+        let callee = self.evaluate(callee)?;
+
+        let mut values = vec![];
+        for arg in args {
+            values.push(self.evaluate(arg)?);
+        }
+
+        match callee {
+            Value::Function(params, body, closure) => {
+                self.call_function(span, params, body, closure, values)
+            }
+            Value::NativeFn(name, arity, func) => self.call_native(span, name, arity, func, values),
+            _ => Err(Signal::Error(RoxError::runtime(
+                span,
+                "Can only call functions.",
+            ))),
+        }
+    }
+
+    fn call_native(
+        &mut self,
+        span: (u32, u32),
+        name: String,
+        arity: usize,
+        func: fn(Vec<Value>) -> Result<Value, String>,
+        args: Vec<Value>,
+    ) -> Result<Value, Signal> {
+        if arity != args.len() {
+            return Err(Signal::Error(RoxError::runtime(
+                span,
+                format!(
+                    "Expected {} arguments but got {} for '{}'.",
+                    arity,
+                    args.len(),
+                    name
+                ),
+            )));
+        }
+
+        func(args).map_err(|msg| Signal::Error(RoxError::runtime(span, msg)))
+    }
+
+    fn call_function(
+        &mut self,
+        span: (u32, u32),
+        params: Vec<String>,
+        body: Box<Stmt>,
+        closure: Environment,
+        args: Vec<Value>,
+    ) -> Result<Value, Signal> {
+        if params.len() != args.len() {
+            return Err(Signal::Error(RoxError::runtime(
+                span,
+                format!("Expected {} arguments but got {}.", params.len(), args.len()),
+            )));
+        }
+
+        // run the call in a fresh scope layered on the closure environment,
+        // restoring the caller's environment once the call returns.
+        let caller_envs = std::mem::replace(&mut self.envs, closure);
+        self.envs.begin_scope();
+        for (param, arg) in params.into_iter().zip(args) {
+            self.envs.define_var(param, Some(arg));
+        }
+
+        let result = self.run(*body);
+        self.envs = caller_envs;
+
+        match result {
+            Ok(()) => Ok(Value::Nil),
+            Err(Signal::Return(value)) => Ok(value),
+            Err(err) => Err(err),
         }
     }
 }
@@ -1,27 +1,151 @@
+use std::collections::HashMap;
+
+use crate::error::RoxError;
+use crate::token::Position;
 use crate::token::Token;
 use crate::token::WithSpan;
 
+/// A lexing failure, carrying the source span (char offsets) where it
+/// occurred. Collected rather than aborting scanning on the first one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexError {
+    UnexpectedChar(char, (u32, u32)),
+    UnterminatedString((u32, u32)),
+    UnterminatedComment((u32, u32)),
+    MalformedNumber((u32, u32)),
+    MalformedEscapeSequence((u32, u32)),
+}
+
+impl LexError {
+    fn span(&self) -> (u32, u32) {
+        match self {
+            LexError::UnexpectedChar(_, span)
+            | LexError::UnterminatedString(span)
+            | LexError::UnterminatedComment(span)
+            | LexError::MalformedNumber(span)
+            | LexError::MalformedEscapeSequence(span) => *span,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            LexError::UnexpectedChar(ch, _) => format!("Unexpected character '{}'.", ch),
+            LexError::UnterminatedString(_) => "Unterminated string.".to_string(),
+            LexError::UnterminatedComment(_) => "Unterminated block comment.".to_string(),
+            LexError::MalformedNumber(_) => "Malformed number literal.".to_string(),
+            LexError::MalformedEscapeSequence(_) => "Malformed escape sequence.".to_string(),
+        }
+    }
+}
+
+impl From<LexError> for RoxError {
+    fn from(err: LexError) -> Self {
+        RoxError::lex(err.span(), err.message())
+    }
+}
+
 pub struct Lexer<'a> {
     source: &'a [char],
-    start_pos: usize,
     cursor: usize, // index of next char will be scanned
+    line: u32,
+    column: u32,
+    // position of the token currently being scanned, and of the last char consumed.
+    token_start: Position,
+    prev_position: Position,
+    // decoded contents of each string token scanned so far, keyed by the
+    // token's start offset (the opening '"'), since `Token::String` itself
+    // carries no data.
+    strings: HashMap<u32, String>,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(source: &'a [char]) -> Self {
+        let origin = Position {
+            line: 1,
+            column: 1,
+            offset: 0,
+        };
         Self {
             source,
-            start_pos: 0,
             cursor: 0,
+            line: 1,
+            column: 1,
+            token_start: origin,
+            prev_position: origin,
+            strings: HashMap::new(),
         }
     }
 
-    fn number(&mut self) -> Token {
-        self.consume_while(|c| c >= '0' && c <= '9');
-        if self.consume_if('.') {
-            self.consume_while(|c| c >= '0' && c <= '9');
+    // `ch` is the leading digit `next_token` already consumed before
+    // dispatching here (mirroring how `identifier(ch)` is handed its leading
+    // char).
+    fn number(&mut self, ch: char) -> Result<Token, LexError> {
+        if ch == '0' && matches!(self.peek(), Some('x' | 'X')) {
+            self.advance_cursor();
+            return self.radix_number(16, |c| c.is_ascii_hexdigit());
+        }
+        if ch == '0' && matches!(self.peek(), Some('b' | 'B')) {
+            self.advance_cursor();
+            return self.radix_number(2, |c| c == '0' || c == '1');
+        }
+
+        let mut text = self.digits_from(ch, |c| c.is_ascii_digit())?;
+
+        if self.peek() == Some('.') && matches!(self.peek_next(), Some('0'..='9')) {
+            self.advance_cursor();
+            text.push('.');
+            text.push_str(&self.digits(|c| c.is_ascii_digit())?);
         }
-        Token::Number
+
+        if matches!(self.peek(), Some('e' | 'E')) {
+            text.push(self.next().unwrap());
+            if matches!(self.peek(), Some('+' | '-')) {
+                text.push(self.next().unwrap());
+            }
+            text.push_str(&self.digits(|c| c.is_ascii_digit())?);
+        }
+
+        text.parse()
+            .map(Token::Number)
+            .map_err(|_| LexError::MalformedNumber(self.span()))
+    }
+
+    // consumes a run of chars matching `pred`, allowing `_` digit separators
+    // between them; rejects an empty run or a misplaced separator.
+    fn digits(&mut self, pred: impl Fn(char) -> bool) -> Result<String, LexError> {
+        let raw = self.digit_run(pred);
+        if raw.is_empty() {
+            return Err(LexError::MalformedNumber(self.span()));
+        }
+
+        self.validate_separators(raw)
+    }
+
+    // like `digits`, but prepends an already-consumed leading digit, so an
+    // otherwise-empty continuation (e.g. just "1") is fine.
+    fn digits_from(&mut self, leading: char, pred: impl Fn(char) -> bool) -> Result<String, LexError> {
+        let raw = format!("{leading}{}", self.digit_run(pred));
+        self.validate_separators(raw)
+    }
+
+    fn digit_run(&mut self, pred: impl Fn(char) -> bool) -> String {
+        self.consume_while(|c| pred(c) || c == '_').into_iter().collect()
+    }
+
+    // rejects a leading/trailing/doubled `_` separator, then strips the rest.
+    fn validate_separators(&self, raw: String) -> Result<String, LexError> {
+        if raw.starts_with('_') || raw.ends_with('_') || raw.contains("__") {
+            return Err(LexError::MalformedNumber(self.span()));
+        }
+
+        Ok(raw.replace('_', ""))
+    }
+
+    fn radix_number(&mut self, radix: u32, pred: impl Fn(char) -> bool) -> Result<Token, LexError> {
+        let digits = self.digits(pred)?;
+        i64::from_str_radix(&digits, radix)
+            .map(|n| Token::Number(n as f64))
+            .map_err(|_| LexError::MalformedNumber(self.span()))
     }
 
     fn identifier(&mut self, ch: char) -> Token {
@@ -31,6 +155,8 @@ impl<'a> Lexer<'a> {
 
         match match_chars[..] {
             ['a', 'n', 'd'] => Token::And,
+            ['b', 'r', 'e', 'a', 'k'] => Token::Break,
+            ['c', 'o', 'n', 't', 'i', 'n', 'u', 'e'] => Token::Continue,
             ['e', 'l', 's', 'e'] => Token::Else,
             ['f', 'a', 'l', 's', 'e'] => Token::False,
             ['f', 'o', 'r'] => Token::For,
@@ -43,80 +169,160 @@ impl<'a> Lexer<'a> {
             ['r', 'e', 't', 'u', 'r', 'n'] => Token::Return,
             ['t', 'r', 'u', 'e'] => Token::True,
             ['l', 'e', 't'] => Token::Let,
+            ['w', 'h', 'i', 'l', 'e'] => Token::While,
             _ => Token::Identifier,
         }
     }
 
-    fn string(&mut self) -> Token {
-        self.consume_while(|c| c != '"');
-        if !self.consume_if('"') {
-            println!("Unterminated String.");
-            // TODO: produce good error for Unterminated String.
+    fn string(&mut self) -> Result<Token, LexError> {
+        let mut decoded = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(LexError::UnterminatedString(self.span())),
+                Some('"') => break,
+                Some('\\') => {
+                    self.advance_cursor();
+                    match self.peek() {
+                        Some('n') => decoded.push('\n'),
+                        Some('t') => decoded.push('\t'),
+                        Some('r') => decoded.push('\r'),
+                        Some('"') => decoded.push('"'),
+                        Some('\\') => decoded.push('\\'),
+                        Some('0') => decoded.push('\0'),
+                        Some(_) => {
+                            self.advance_cursor();
+                            // resume after the string so one bad escape
+                            // doesn't cascade into spurious token errors.
+                            self.consume_while(|c| c != '"');
+                            self.consume_if('"');
+                            return Err(LexError::MalformedEscapeSequence(self.span()));
+                        }
+                        None => return Err(LexError::UnterminatedString(self.span())),
+                    }
+                    self.advance_cursor();
+                }
+                Some(c) => {
+                    decoded.push(c);
+                    self.advance_cursor();
+                }
+            }
         }
-        Token::String
+
+        self.consume_if('"');
+        self.strings.insert(self.token_start.offset, decoded);
+        Ok(Token::String)
     }
 
     fn skip_whitespace(&mut self) {
         self.consume_while(|c| c.is_whitespace());
     }
 
-    fn skip_comment(&mut self) {
-        if self.check_comment() {
-            self.consume_while(|c| c != '\n');
-            self.skip_whitespace(); // skip newline at end if present
+    fn skip_comment(&mut self) -> Result<(), LexError> {
+        loop {
+            if self.check_comment() {
+                self.consume_while(|c| c != '\n');
+                self.skip_whitespace(); // skip newline at end if present
+            } else if self.check_block_comment() {
+                self.skip_block_comment()?;
+                self.skip_whitespace();
+            } else {
+                break;
+            }
         }
+        Ok(())
     }
 
-    fn next_token(&mut self) -> Option<Token> {
-        self.skip_whitespace();
-        self.skip_comment();
-
-        if let Some(ch) = self.next() {
-            self.start_pos = self.cursor - 1;
-
-            match ch {
-                '(' => Some(Token::LeftParen),
-                ')' => Some(Token::RightParen),
-                '[' => Some(Token::LeftBracket),
-                ']' => Some(Token::RightBracket),
-                '{' => Some(Token::LeftBrace),
-                '}' => Some(Token::RightBrace),
-                ',' => Some(Token::Comma),
-                '.' => Some(Token::Dot),
-                '-' => Some(Token::Minus),
-                '+' => Some(Token::Plus),
-                ';' => Some(Token::Semicolon),
-                '/' => Some(Token::Slash),
-                '*' => Some(Token::Star),
-                '!' => Some(self.if_match('=', Token::NotEqual, Token::Not)),
-                '=' => Some(self.if_match('=', Token::EqualEqual, Token::Equal)),
-                '>' => Some(self.if_match('=', Token::GreaterEqual, Token::Greater)),
-                '<' => Some(self.if_match('=', Token::LessEqual, Token::Less)),
-                '"' => Some(self.string()),
-                '0'..='9' => Some(self.number()),
-                'a'..='z' | 'A'..='Z' | '_' => Some(self.identifier(ch)),
-                _ => Some(Token::Error),
+    // consumes a `/* ... */` block comment, which may nest; `depth` counts
+    // how many unclosed `/*` are currently open.
+    fn skip_block_comment(&mut self) -> Result<(), LexError> {
+        let start = self.position();
+        self.advance_cursor(); // consume '/'
+        self.advance_cursor(); // consume '*'
+
+        let mut depth = 1;
+        while depth > 0 {
+            match (self.peek(), self.peek_next()) {
+                (Some('/'), Some('*')) => {
+                    self.advance_cursor();
+                    self.advance_cursor();
+                    depth += 1;
+                }
+                (Some('*'), Some('/')) => {
+                    self.advance_cursor();
+                    self.advance_cursor();
+                    depth -= 1;
+                }
+                (Some(_), _) => self.advance_cursor(),
+                (None, _) => {
+                    return Err(LexError::UnterminatedComment((
+                        start.offset,
+                        self.prev_position.offset,
+                    )));
+                }
             }
-        } else {
-            None
         }
+        Ok(())
+    }
+
+    fn next_token(&mut self) -> Option<Result<Token, LexError>> {
+        self.skip_whitespace();
+        if let Err(err) = self.skip_comment() {
+            return Some(Err(err));
+        }
+
+        self.token_start = self.position();
+        let ch = self.next()?;
+
+        Some(match ch {
+            '(' => Ok(Token::LeftParen),
+            ')' => Ok(Token::RightParen),
+            '[' => Ok(Token::LeftBracket),
+            ']' => Ok(Token::RightBracket),
+            '{' => Ok(Token::LeftBrace),
+            '}' => Ok(Token::RightBrace),
+            ',' => Ok(Token::Comma),
+            '.' => Ok(Token::Dot),
+            '-' => Ok(Token::Minus),
+            '+' => Ok(Token::Plus),
+            ';' => Ok(Token::Semicolon),
+            '/' => Ok(Token::Slash),
+            '*' => Ok(Token::Star),
+            '%' => Ok(Token::Percent),
+            '&' => Ok(Token::Ampersand),
+            '|' => Ok(Token::Pipe),
+            '^' => Ok(Token::Caret),
+            '!' => Ok(self.if_match('=', Token::NotEqual, Token::Not)),
+            '=' => Ok(self.if_match('=', Token::EqualEqual, Token::Equal)),
+            '>' => Ok(self.if_match('=', Token::GreaterEqual, Token::Greater)),
+            '<' => Ok(self.if_match('=', Token::LessEqual, Token::Less)),
+            '"' => self.string(),
+            '0'..='9' => self.number(ch),
+            'a'..='z' | 'A'..='Z' | '_' => Ok(self.identifier(ch)),
+            _ => {
+                // skip the rest of this garbage run so one bad char doesn't
+                // cascade into more errors; resume at the next boundary.
+                self.recover();
+                Err(LexError::UnexpectedChar(ch, self.span()))
+            }
+        })
     }
 
-    pub fn tokenize_with_context(&mut self) -> Vec<WithSpan<Token>> {
+    pub fn tokenize_with_context(&mut self) -> (Vec<WithSpan<Token>>, Vec<LexError>, HashMap<u32, String>) {
         let mut tokens = vec![];
-        while let Some(token) = self.next_token() {
-            tokens.push(WithSpan::new(
-                token,
-                self.start_pos as u32,
-                self.cursor as u32 - 1,
-            ));
+        let mut errors = vec![];
+        while let Some(result) = self.next_token() {
+            match result {
+                Ok(token) => tokens.push(WithSpan::new(token, self.token_start, self.prev_position)),
+                Err(err) => errors.push(err),
+            }
         }
-        tokens.push(WithSpan::new(
-            Token::Eof,
-            self.cursor as u32 - 1,
-            self.cursor as u32 - 1,
-        ));
-        tokens
+        let eof = Position {
+            line: self.line,
+            column: self.column,
+            offset: self.cursor as u32 - 1,
+        };
+        tokens.push(WithSpan::new(Token::Eof, eof, eof));
+        (tokens, errors, std::mem::take(&mut self.strings))
     }
 }
 
@@ -133,6 +339,10 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    fn peek_next(&mut self) -> Option<char> {
+        self.source.get(self.cursor + 1).copied()
+    }
+
     fn check_comment(&mut self) -> bool {
         if Some(&['/', '/'][..]) == self.source.get(self.cursor..self.cursor + 2) {
             true
@@ -141,7 +351,41 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    fn check_block_comment(&mut self) -> bool {
+        if Some(&['/', '*'][..]) == self.source.get(self.cursor..self.cursor + 2) {
+            true
+        } else {
+            false
+        }
+    }
+
+    fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+            offset: self.cursor as u32,
+        }
+    }
+
+    // the (start, end) char-offset span of the token currently being scanned.
+    fn span(&self) -> (u32, u32) {
+        (self.token_start.offset, self.prev_position.offset)
+    }
+
+    // error recovery: skip the remainder of the current run of non-whitespace
+    // so scanning resumes at the next whitespace/known boundary.
+    fn recover(&mut self) {
+        self.consume_while(|c| !c.is_whitespace());
+    }
+
     fn advance_cursor(&mut self) {
+        self.prev_position = self.position();
+        if self.source[self.cursor] == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
         self.cursor += 1;
     }
 
@@ -189,7 +433,9 @@ impl<'a> Lexer<'a> {
     }
 }
 
-pub fn tokenize_with_context(buf: &[char]) -> Vec<WithSpan<Token>> {
+pub fn tokenize_with_context(
+    buf: &[char],
+) -> (Vec<WithSpan<Token>>, Vec<LexError>, HashMap<u32, String>) {
     let mut t = Lexer::new(buf);
     t.tokenize_with_context()
 }
@@ -202,6 +448,7 @@ mod tests {
     fn tokenize(src: &str) -> Vec<Token> {
         let src: Vec<char> = src.chars().collect();
         tokenize_with_context(&src[..])
+            .0
             .iter()
             .map(|t| t.value())
             .collect()
@@ -265,17 +512,17 @@ mod tests {
                 Token::Let,        // let
                 Token::Identifier, // i
                 Token::Equal,      // =
-                Token::Number,     // 0
-                Token::Semicolon,  // ;
-                Token::Identifier, // i
-                Token::Less,       // <
-                Token::Number,     // 10
-                Token::Semicolon,  // ;
-                Token::Identifier, // i
-                Token::Equal,      // =
-                Token::Identifier, // i
-                Token::Plus,       // +
-                Token::Number,     // 1
+                Token::Number(0.0),  // 0
+                Token::Semicolon,    // ;
+                Token::Identifier,   // i
+                Token::Less,         // <
+                Token::Number(10.0), // 10
+                Token::Semicolon,    // ;
+                Token::Identifier,   // i
+                Token::Equal,        // =
+                Token::Identifier,   // i
+                Token::Plus,         // +
+                Token::Number(1.0),  // 1
                 Token::RightParen, // )
                 Token::LeftBrace,  // {
                 Token::Print,      // print
@@ -287,4 +534,14 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn number_literals() {
+        assert_eq!(tokenize("0"), vec![Token::Number(0.0), Token::Eof]);
+        assert_eq!(tokenize("42"), vec![Token::Number(42.0), Token::Eof]);
+        assert_eq!(tokenize("0xFF"), vec![Token::Number(255.0), Token::Eof]);
+        assert_eq!(tokenize("0b101"), vec![Token::Number(5.0), Token::Eof]);
+        assert_eq!(tokenize("1e9"), vec![Token::Number(1e9), Token::Eof]);
+        assert_eq!(tokenize("1_000"), vec![Token::Number(1000.0), Token::Eof]);
+    }
 }
@@ -2,7 +2,10 @@ pub mod interpreter;
 
 mod ast;
 mod environment;
+mod error;
 mod lexer;
 mod parser;
+mod resolver;
+mod stdlib;
 mod token;
 mod value;
@@ -1,6 +1,33 @@
+use std::io::Write;
+
 use rox::interpreter::Interpreter;
 
 fn main() {
     let file_paths: Vec<_> = std::env::args().collect();
-    Interpreter::new().interpret(&std::fs::read_to_string(&file_paths[1]).unwrap());
+
+    match file_paths.get(1) {
+        Some(path) => {
+            Interpreter::new().interpret(&std::fs::read_to_string(path).unwrap());
+        }
+        None => repl(),
+    }
+}
+
+fn repl() {
+    let mut interpreter = Interpreter::new();
+    let mut line = String::new();
+
+    loop {
+        print!("> ");
+        std::io::stdout().flush().unwrap();
+
+        line.clear();
+        if std::io::stdin().read_line(&mut line).unwrap() == 0 {
+            break; // EOF (Ctrl-D)
+        }
+
+        if let Some(value) = interpreter.interpret(&line) {
+            println!("{:?}", value);
+        }
+    }
 }
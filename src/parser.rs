@@ -1,5 +1,8 @@
+use std::collections::HashMap;
+
 use crate::{
-    ast::{Ast, BinaryOperator, Expr, Stmt, UnaryOperator},
+    ast::{Ast, BinaryOperator, Expr, ExprKind, LogicalOperator, Stmt, UnaryOperator},
+    error::RoxError,
     lexer::tokenize_with_context,
     token::Token,
     token::WithSpan,
@@ -9,32 +12,52 @@ pub struct Parser {
     tokens: Vec<WithSpan<Token>>,
     source: Vec<char>,
     cursor: usize,
-    errors: Vec<String>,
+    errors: Vec<RoxError>,
+    // decoded contents of each string token, keyed by its start offset;
+    // populated by the lexer since it's the one that knows escape sequences.
+    strings: HashMap<u32, String>,
 }
 
 pub fn parse(src: &str) -> Vec<Stmt> {
     Parser::new(src).parse()
 }
 
+/// What a single line of REPL input turned out to be: a bare expression
+/// (printed for its value), or ordinary statements.
+pub enum ReplInput {
+    Expr(Expr),
+    Stmts(Ast),
+}
+
+// REPL input may omit the trailing ';' a statement would require, e.g. `1 + 2`.
+// Try parsing it as a single expression spanning the whole input first, and
+// only fall back to regular statement parsing if that doesn't consume it all.
+pub fn parse_repl(src: &str) -> ReplInput {
+    let mut parser = Parser::new(src);
+    if let Ok(expr) = parser.expr() {
+        if parser.is_at_end() {
+            return ReplInput::Expr(expr);
+        }
+    }
+
+    ReplInput::Stmts(parse(src))
+}
+
 impl Parser {
     pub fn new(source: &str) -> Self {
         let source: Vec<char> = source.chars().collect();
+        let (tokens, lex_errors, strings) = tokenize_with_context(&source[..]);
         Self {
-            tokens: tokenize_with_context(&source[..]),
+            tokens,
             source,
             cursor: 0,
-            errors: vec![],
+            errors: lex_errors.into_iter().map(RoxError::from).collect(),
+            strings,
         }
     }
 
     // Program => statement* EOF;
     pub fn parse(mut self) -> Ast {
-        // DEBUG:
-        for token in &self.tokens {
-            println!("{:?}", token.value);
-        }
-        //
-
         let mut statements = vec![];
         while !self.is_at_end() {
             if let Ok(stmt) = self.declaration() {
@@ -45,7 +68,7 @@ impl Parser {
         }
         // DEBUG:
         for err in &self.errors {
-            print!("{}", err);
+            print!("{}", err.report(&self.source));
         }
         //
         statements
@@ -58,6 +81,7 @@ impl Parser {
         if let Some(token) = self.peek() {
             match token {
                 Token::Let => self.var_declaration(),
+                Token::Fun => self.fun_declaration(),
                 _ => self.statement(),
             }
         } else {
@@ -65,6 +89,34 @@ impl Parser {
         }
     }
 
+    // FunDecl => "fun" IDENTIFIER "(" parameters? ")" Block ;
+    fn fun_declaration(&mut self) -> Result<Stmt, ()> {
+        self.advance(); // advance Fun token
+        self.consume(Token::Identifier, "Expected function name.")?;
+        let name = self.parse_name(self.cursor - 1);
+
+        self.consume(Token::LeftParen, "Expected '(' after function name.")?;
+        let mut params = vec![];
+        if !self.check(Token::RightParen) {
+            loop {
+                self.consume(Token::Identifier, "Expected parameter name.")?;
+                params.push(self.parse_name(self.cursor - 1));
+                if !self.match_token(Token::Comma) {
+                    break;
+                }
+            }
+        }
+        self.consume(Token::RightParen, "Expected ')' after parameters.")?;
+
+        if !self.check(Token::LeftBrace) {
+            self.error_at(self.cursor, "Expected '{' before function body.");
+            return Err(());
+        }
+        let body = self.block()?;
+
+        Ok(Stmt::Function(name, params, Box::new(body)))
+    }
+
     fn var_declaration(&mut self) -> Result<Stmt, ()> {
         self.advance(); // advance Let token
         self.consume(Token::Identifier, "Expected Identifier")?;
@@ -79,12 +131,19 @@ impl Parser {
         Ok(Stmt::Let(identifier_lexeme, initializer_expr))
     }
 
-    // Statement => ExprStmt | PrintStmt | Block;
+    // Statement => ExprStmt | PrintStmt | IfStmt | WhileStmt | ForStmt
+    //            | BreakStmt | ContinueStmt | ReturnStmt | Block;
     fn statement(&mut self) -> Result<Stmt, ()> {
         if let Some(token) = self.peek() {
             match token {
                 Token::LeftBrace => self.block(),
+                Token::If => self.if_stmt(),
+                Token::While => self.while_stmt(),
+                Token::For => self.for_stmt(),
+                Token::Break => self.break_stmt(),
+                Token::Continue => self.continue_stmt(),
                 Token::Print => self.print_stmt(),
+                Token::Return => self.return_stmt(),
                 _ => self.expr_stmt(),
             }
         } else {
@@ -92,6 +151,101 @@ impl Parser {
         }
     }
 
+    // WhileStmt => "while" "(" expr ")" statement ;
+    fn while_stmt(&mut self) -> Result<Stmt, ()> {
+        self.advance(); // advance While token
+        self.consume(Token::LeftParen, "Expected '(' after 'while'.")?;
+        let cond = self.expr()?;
+        self.consume(Token::RightParen, "Expected ')' after condition.")?;
+
+        let body = Box::new(self.statement()?);
+        Ok(Stmt::While(cond, body))
+    }
+
+    // ForStmt => "for" "(" ( VarDecl | ExprStmt | ";" ) expr? ";" expr? ")" statement ;
+    // Desugars into a `Stmt::For` (carrying the condition/increment/body), with
+    // the initializer (if any) wrapped around it in a `Block`.
+    fn for_stmt(&mut self) -> Result<Stmt, ()> {
+        self.advance(); // advance For token
+        self.consume(Token::LeftParen, "Expected '(' after 'for'.")?;
+
+        let initializer = if self.match_token(Token::Semicolon) {
+            None
+        } else if self.check(Token::Let) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expr_stmt()?)
+        };
+
+        let condition = if !self.check(Token::Semicolon) {
+            self.expr()?
+        } else {
+            let at = self.cursor;
+            Expr::new(ExprKind::Boolean(true), self.span_from(at, at))
+        };
+        self.consume(Token::Semicolon, "Expected ';' after loop condition.")?;
+
+        let increment = if !self.check(Token::RightParen) {
+            Some(self.expr()?)
+        } else {
+            None
+        };
+        self.consume(Token::RightParen, "Expected ')' after for clauses.")?;
+
+        let body = self.statement()?;
+
+        let mut stmt = Stmt::For(condition, increment, Box::new(body));
+
+        if let Some(initializer) = initializer {
+            stmt = Stmt::Block(vec![initializer, stmt]);
+        }
+
+        Ok(stmt)
+    }
+
+    // BreakStmt => "break" ";" ;
+    fn break_stmt(&mut self) -> Result<Stmt, ()> {
+        self.advance(); // advance Break token
+        self.consume(Token::Semicolon, "Expected ';' after 'break'.")?;
+        Ok(Stmt::Break)
+    }
+
+    // ContinueStmt => "continue" ";" ;
+    fn continue_stmt(&mut self) -> Result<Stmt, ()> {
+        self.advance(); // advance Continue token
+        self.consume(Token::Semicolon, "Expected ';' after 'continue'.")?;
+        Ok(Stmt::Continue)
+    }
+
+    // IfStmt => "if" "(" expr ")" statement ( "else" statement )? ;
+    fn if_stmt(&mut self) -> Result<Stmt, ()> {
+        self.advance(); // advance If token
+        self.consume(Token::LeftParen, "Expected '(' after 'if'.")?;
+        let cond = self.expr()?;
+        self.consume(Token::RightParen, "Expected ')' after if condition.")?;
+
+        let then_branch = Box::new(self.statement()?);
+        let mut else_branch = None;
+        if self.match_token(Token::Else) {
+            else_branch = Some(Box::new(self.statement()?));
+        }
+
+        Ok(Stmt::If(cond, then_branch, else_branch))
+    }
+
+    // ReturnStmt => "return" expr? ";" ;
+    fn return_stmt(&mut self) -> Result<Stmt, ()> {
+        self.advance(); // advance Return token
+
+        let mut value = None;
+        if !self.check(Token::Semicolon) {
+            value = Some(self.expr()?);
+        }
+
+        self.consume(Token::Semicolon, "Expected ';' after return value.")?;
+        Ok(Stmt::Return(value))
+    }
+
     // Block => Declarations* ;
     fn block(&mut self) -> Result<Stmt, ()> {
         self.advance(); // advance '{' token
@@ -146,41 +300,76 @@ impl Parser {
 
     // primary => IDENTIFIER | NUMBER | STRING | "true" | "false" | "nil" | "(" expression ")"
     fn primary(&mut self) -> Result<Expr, ()> {
+        let start = self.cursor;
         if let Some(token) = self.next() {
-            match token {
-                Token::Nil => Ok(Expr::Nil),
-                Token::False => Ok(Expr::Boolean(false)),
-                Token::True => Ok(Expr::Boolean(true)),
-                Token::Number => Ok(Expr::Number(self.parse_number(self.cursor - 1))),
-                Token::String => Ok(Expr::String(self.parse_string(self.cursor - 1))),
-                Token::Identifier => Ok(Expr::Variable(self.parse_name(self.cursor - 1))),
-                Token::LeftParen => self.grouping(),
-                _ => Err(()),
-            }
+            let kind = match token {
+                Token::Nil => ExprKind::Nil,
+                Token::False => ExprKind::Boolean(false),
+                Token::True => ExprKind::Boolean(true),
+                Token::Number(n) => ExprKind::Number(n),
+                Token::String => ExprKind::String(self.parse_string(self.cursor - 1)),
+                Token::Identifier => ExprKind::Variable(self.parse_name(self.cursor - 1), None),
+                Token::LeftParen => return self.grouping(),
+                _ => return Err(()),
+            };
+            Ok(Expr::new(kind, self.span_from(start, self.cursor - 1)))
         } else {
             self.error_at(self.cursor, "Expected Primary Token");
             Err(())
         }
     }
 
-    // unary => ( "!" | "-" ) unary | primary ;
+    // unary => ( "!" | "-" ) unary | call ;
     fn unary(&mut self) -> Result<Expr, ()> {
+        let start = self.cursor;
         if self.match_token(Token::Not) | self.match_token(Token::Minus) {
             let operator = parser_unary_operator(self.previous_token())?;
             let right = self.unary()?;
-            Ok(Expr::Unary(operator, Box::new(right)))
+            let span = self.span_from(start, self.cursor - 1);
+            Ok(Expr::new(ExprKind::Unary(operator, Box::new(right)), span))
         } else {
-            self.primary()
+            self.call()
+        }
+    }
+
+    // call => primary ( "(" arguments? ")" )* ;
+    fn call(&mut self) -> Result<Expr, ()> {
+        let start = self.cursor;
+        let mut expr = self.primary()?;
+
+        while self.match_token(Token::LeftParen) {
+            expr = self.finish_call(start, expr)?;
+        }
+
+        Ok(expr)
+    }
+
+    // arguments => expr ( "," expr )* ;
+    fn finish_call(&mut self, start: usize, callee: Expr) -> Result<Expr, ()> {
+        let mut args = vec![];
+        if !self.check(Token::RightParen) {
+            loop {
+                args.push(self.expr()?);
+                if !self.match_token(Token::Comma) {
+                    break;
+                }
+            }
         }
+        self.consume(Token::RightParen, "Expected ')' after arguments.")?;
+
+        let span = self.span_from(start, self.cursor - 1);
+        Ok(Expr::new(ExprKind::Call(Box::new(callee), args), span))
     }
 
-    // factor => unary ( ( "/" | "*" ) unary )* ;
+    // factor => unary ( ( "/" | "*" | "%" ) unary )* ;
     fn factor(&mut self) -> Result<Expr, ()> {
+        let start = self.cursor;
         let mut left = self.unary()?;
-        while self.match_token(Token::Star) | self.match_token(Token::Slash) {
+        while self.match_token(Token::Star) | self.match_token(Token::Slash) | self.match_token(Token::Percent) {
             let operator = parse_binary_operator(self.previous_token())?;
             let right = self.unary()?;
-            left = Expr::Binary(Box::new(left), operator, Box::new(right));
+            let span = self.span_from(start, self.cursor - 1);
+            left = Expr::new(ExprKind::Binary(Box::new(left), operator, Box::new(right)), span);
         }
 
         Ok(left)
@@ -188,12 +377,14 @@ impl Parser {
 
     // term    => factor ( ( "-" | "+" ) factor )* ;
     fn term(&mut self) -> Result<Expr, ()> {
+        let start = self.cursor;
         let mut left = self.factor()?;
 
         while self.match_token(Token::Plus) | self.match_token(Token::Minus) {
             let operator = parse_binary_operator(self.previous_token())?;
             let right = self.factor()?;
-            left = Expr::Binary(Box::new(left), operator, Box::new(right));
+            let span = self.span_from(start, self.cursor - 1);
+            left = Expr::new(ExprKind::Binary(Box::new(left), operator, Box::new(right)), span);
         }
 
         Ok(left)
@@ -201,6 +392,7 @@ impl Parser {
 
     // comparison => term ( ( ">" | ">=" | "<", | "<=" ) term )
     fn comparison(&mut self) -> Result<Expr, ()> {
+        let start = self.cursor;
         let mut left = self.term()?;
 
         while self.match_token(Token::Greater)
@@ -210,7 +402,8 @@ impl Parser {
         {
             let operator = parse_binary_operator(self.previous_token())?;
             let right = self.term()?;
-            left = Expr::Binary(Box::new(left), operator, Box::new(right));
+            let span = self.span_from(start, self.cursor - 1);
+            left = Expr::new(ExprKind::Binary(Box::new(left), operator, Box::new(right)), span);
         }
 
         Ok(left)
@@ -218,12 +411,60 @@ impl Parser {
 
     // equality => comparision ( ( "!=" | "==" ) comparision )
     fn equality(&mut self) -> Result<Expr, ()> {
+        let start = self.cursor;
         let mut left = self.comparison()?;
 
         while self.match_token(Token::NotEqual) || self.match_token(Token::EqualEqual) {
             let operator = parse_binary_operator(self.previous_token())?;
             let right = self.comparison()?;
-            left = Expr::Binary(Box::new(left), operator, Box::new(right));
+            let span = self.span_from(start, self.cursor - 1);
+            left = Expr::new(ExprKind::Binary(Box::new(left), operator, Box::new(right)), span);
+        }
+
+        Ok(left)
+    }
+
+    // logic_or => logic_and ( "or" logic_and )* ;
+    fn or(&mut self) -> Result<Expr, ()> {
+        let start = self.cursor;
+        let mut left = self.and()?;
+
+        while self.match_token(Token::Or) {
+            let right = self.and()?;
+            let span = self.span_from(start, self.cursor - 1);
+            left = Expr::new(ExprKind::Logical(Box::new(left), LogicalOperator::Or, Box::new(right)), span);
+        }
+
+        Ok(left)
+    }
+
+    // logic_and => bitwise ( "and" bitwise )* ;
+    fn and(&mut self) -> Result<Expr, ()> {
+        let start = self.cursor;
+        let mut left = self.bitwise()?;
+
+        while self.match_token(Token::And) {
+            let right = self.bitwise()?;
+            let span = self.span_from(start, self.cursor - 1);
+            left = Expr::new(ExprKind::Logical(Box::new(left), LogicalOperator::And, Box::new(right)), span);
+        }
+
+        Ok(left)
+    }
+
+    // bitwise => equality ( ( "&" | "|" | "^" ) equality )* ;
+    fn bitwise(&mut self) -> Result<Expr, ()> {
+        let start = self.cursor;
+        let mut left = self.equality()?;
+
+        while self.match_token(Token::Ampersand)
+            | self.match_token(Token::Pipe)
+            | self.match_token(Token::Caret)
+        {
+            let operator = parse_binary_operator(self.previous_token())?;
+            let right = self.equality()?;
+            let span = self.span_from(start, self.cursor - 1);
+            left = Expr::new(ExprKind::Binary(Box::new(left), operator, Box::new(right)), span);
         }
 
         Ok(left)
@@ -231,7 +472,8 @@ impl Parser {
 
     fn assignment(&mut self) -> Result<Expr, ()> {
         // e.g [ a = "hari" ]
-        let left_expr = self.equality()?;
+        let start = self.cursor;
+        let left_expr = self.or()?;
 
         if self.match_token(Token::Equal) {
             let equal_index = self.cursor - 1; // for err reporting
@@ -239,8 +481,9 @@ impl Parser {
             // e.g [ a = b = "hari" ], hence parse right_hand_side as assignment itself.
             let right_expr = self.assignment()?;
 
-            if let Expr::Variable(name) = left_expr {
-                return Ok(Expr::Assignment(name, Box::new(right_expr)));
+            if let ExprKind::Variable(name, _) = left_expr.kind {
+                let span = self.span_from(start, self.cursor - 1);
+                return Ok(Expr::new(ExprKind::Assignment(name, Box::new(right_expr), None), span));
             };
 
             self.error_at(equal_index, "Invalid assignment target.")
@@ -250,13 +493,17 @@ impl Parser {
     }
 
     /* expression     → assignment;
-     * assignment     → IDENTIFIER '=' assignment | equality ;
+     * assignment     → IDENTIFIER '=' assignment | logic_or ;
+     * logic_or       → logic_and ( "or" logic_and )* ;
+     * logic_and      → bitwise ( "and" bitwise )* ;
+     * bitwise        → equality ( ( "&" | "|" | "^" ) equality )* ;
      * equality       → comparison ( ( "!=" | "==" ) comparison )* ;
      * comparison     → term ( ( ">" | ">=" | "<" | "<=" ) term )* ;
      * term           → factor ( ( "-" | "+" ) factor )* ;
-     * factor         → unary ( ( "/" | "*" ) unary )* ;
+     * factor         → unary ( ( "/" | "*" | "%" ) unary )* ;
      * unary          → ( "!" | "-" ) unary
-     *                | primary ;
+     *                | call ;
+     * call           → primary ( "(" arguments? ")" )* ;
      * primary        → NUMBER | STRING | "true" | "false" | "nil"
      *                  | "(" expression ")" ; */
     pub fn expr(&mut self) -> Result<Expr, ()> {
@@ -279,6 +526,7 @@ impl Parser {
                     Token::For
                     | Token::Fun
                     | Token::If
+                    | Token::While
                     | Token::Print
                     | Token::Return
                     | Token::Let
@@ -347,46 +595,27 @@ impl Parser {
 
     fn parse_string(&mut self, at: usize) -> String {
         let token = self.tokens[at];
-        self.source[token.start_pos() + 1..token.end_pos()]
-            .iter()
-            .collect::<String>()
+        self.strings.get(&token.start_pos()).cloned().unwrap_or_default()
     }
 
     fn parse_name(&mut self, at: usize) -> String {
         let token = self.tokens[at];
-        self.source[token.start_pos()..=token.end_pos()]
-            .iter()
-            .collect::<String>()
-    }
-
-    fn parse_number(&mut self, at: usize) -> f64 {
-        let token = self.tokens[at];
-        self.source[token.start_pos()..=token.end_pos()]
+        self.source[token.start_pos() as usize..=token.end_pos() as usize]
             .iter()
             .collect::<String>()
-            .parse()
-            .unwrap() // TODO: report err
     }
 
-    fn get_line(&self, token_start_pos: usize) -> u32 {
-        let mut line = 1;
-        for i in 0..=token_start_pos {
-            if self.source[i] == '\n' {
-                line += 1;
-            }
-        }
-
-        line
+    // the (start, end) char-offset span covering tokens [start_cursor, end_cursor].
+    fn span_from(&self, start_cursor: usize, end_cursor: usize) -> (u32, u32) {
+        let start_tok = self.tokens[start_cursor];
+        let end_tok = self.tokens[end_cursor];
+        (start_tok.start_pos(), end_tok.end_pos())
     }
 
     fn error_at(&mut self, at: usize, msg: &str) {
-        // let line = "Todo"; // TODO
         let token = self.tokens[at];
-        let line_number = self.get_line(token.start_pos());
-
-        self.errors.push(format!(
-            "\nParseErr: {msg}\nAtLine [{line_number}] AtToken[{token:?}]\n\n"
-        ));
+        self.errors
+            .push(RoxError::parse((token.start_pos(), token.end_pos()), msg));
     }
 }
 
@@ -396,12 +625,16 @@ fn parse_binary_operator(token: Token) -> Result<BinaryOperator, ()> {
         Token::Plus => Ok(BinaryOperator::Plus),
         Token::Slash => Ok(BinaryOperator::Slash),
         Token::Star => Ok(BinaryOperator::Star),
+        Token::Percent => Ok(BinaryOperator::Percent),
         Token::EqualEqual => Ok(BinaryOperator::EqualEqual),
         Token::Greater => Ok(BinaryOperator::Greater),
         Token::GreaterEqual => Ok(BinaryOperator::GreaterEqual),
         Token::Less => Ok(BinaryOperator::Less),
         Token::LessEqual => Ok(BinaryOperator::LessEqual),
         Token::NotEqual => Ok(BinaryOperator::NotEqual),
+        Token::Ampersand => Ok(BinaryOperator::Ampersand),
+        Token::Pipe => Ok(BinaryOperator::Pipe),
+        Token::Caret => Ok(BinaryOperator::Caret),
         _ => {
             eprintln!("Err parsing binay operator from token");
             Err(())
@@ -422,20 +655,47 @@ fn parser_unary_operator(token: Token) -> Result<UnaryOperator, ()> {
 
 #[cfg(test)]
 mod test {
-    use super::super::ast::{BinaryOperator::*, Expr, Expr::*, UnaryOperator};
+    use super::super::ast::{BinaryOperator::*, Expr, ExprKind, ExprKind::*, UnaryOperator};
     use super::Parser;
 
-    fn parse_expression(src: &str) -> Result<Expr, ()> {
-        Parser::new(src).expr()
+    fn parse_expression(src: &str) -> Result<ExprKind, ()> {
+        Parser::new(src).expr().map(|expr| strip_spans(expr.kind))
+    }
+
+    fn boxed(kind: ExprKind) -> Box<Expr> {
+        Box::new(Expr::new(kind, (0, 0)))
+    }
+
+    // tests only care about tree shape, not the real spans parsing assigns to
+    // every node -- zero every span out (recursively, since `ExprKind`'s
+    // variants nest further `Expr`s) so expected trees can use `boxed`'s
+    // placeholder `(0, 0)` throughout instead of reproducing real offsets.
+    fn strip_spans(kind: ExprKind) -> ExprKind {
+        fn strip(expr: Expr) -> Expr {
+            Expr::new(strip_spans(expr.kind), (0, 0))
+        }
+
+        match kind {
+            ExprKind::Binary(l, op, r) => ExprKind::Binary(Box::new(strip(*l)), op, Box::new(strip(*r))),
+            ExprKind::Logical(l, op, r) => ExprKind::Logical(Box::new(strip(*l)), op, Box::new(strip(*r))),
+            ExprKind::Unary(op, inner) => ExprKind::Unary(op, Box::new(strip(*inner))),
+            ExprKind::Call(callee, args) => {
+                ExprKind::Call(Box::new(strip(*callee)), args.into_iter().map(strip).collect())
+            }
+            ExprKind::Assignment(name, inner, depth) => {
+                ExprKind::Assignment(name, Box::new(strip(*inner)), depth)
+            }
+            other => other,
+        }
     }
 
     #[test]
     fn unary() {
         let left = parse_expression("-10 + 2");
         let right = Binary(
-            Box::new(Unary(UnaryOperator::Minus, Box::new(Number(10.0)))),
+            boxed(Unary(UnaryOperator::Minus, boxed(Number(10.0)))),
             Plus,
-            Box::new(Number(2.0)),
+            boxed(Number(2.0)),
         );
 
         assert_eq!(left, Ok(right));
@@ -444,7 +704,7 @@ mod test {
     #[test]
     fn unary_2() {
         let left = parse_expression("-1;");
-        let right = Unary(UnaryOperator::Minus, Box::new(Number(1.0)));
+        let right = Unary(UnaryOperator::Minus, boxed(Number(1.0)));
 
         assert_eq!(left, Ok(right));
     }
@@ -452,7 +712,7 @@ mod test {
     #[test]
     fn binary() {
         let left = parse_expression("10 + 2");
-        let right = Binary(Box::new(Number(10.0)), Plus, Box::new(Number(2.0)));
+        let right = Binary(boxed(Number(10.0)), Plus, boxed(Number(2.0)));
 
         assert_eq!(left, Ok(right));
     }
@@ -461,9 +721,9 @@ mod test {
     fn binary_2() {
         let left = parse_expression("10 / 2 * 5");
         let right = Binary(
-            Box::new(Binary(Box::new(Number(10.0)), Slash, Box::new(Number(2.0)))),
+            boxed(Binary(boxed(Number(10.0)), Slash, boxed(Number(2.0)))),
             Star,
-            Box::new(Number(5.0)),
+            boxed(Number(5.0)),
         );
 
         assert_eq!(left, Ok(right));
@@ -473,9 +733,9 @@ mod test {
     fn binary_grouping() {
         let left = parse_expression("10 / (2 * 5)");
         let right = Binary(
-            Box::new(Number(10.0)),
+            boxed(Number(10.0)),
             Slash,
-            Box::new(Binary(Box::new(Number(2.0)), Star, Box::new(Number(5.0)))),
+            boxed(Binary(boxed(Number(2.0)), Star, boxed(Number(5.0)))),
         );
 
         assert_eq!(left, Ok(right));
@@ -485,16 +745,16 @@ mod test {
     fn binary_unary() {
         let left = parse_expression("10 / -(2 * 5) + 2");
         let right = Binary(
-            Box::new(Binary(
-                Box::new(Number(10.0)),
+            boxed(Binary(
+                boxed(Number(10.0)),
                 Slash,
-                Box::new(Unary(
+                boxed(Unary(
                     UnaryOperator::Minus,
-                    Box::new(Binary(Box::new(Number(2.0)), Star, Box::new(Number(5.0)))),
+                    boxed(Binary(boxed(Number(2.0)), Star, boxed(Number(5.0)))),
                 )),
             )),
             Plus,
-            Box::new(Number(2.0)),
+            boxed(Number(2.0)),
         );
 
         assert_eq!(left, Ok(right));
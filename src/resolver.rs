@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use crate::ast::{Ast, Expr, ExprKind, Stmt};
+use crate::error::RoxError;
+
+/// Binds each variable read/assignment to the number of enclosing scopes to
+/// hop to find its declaration, so the interpreter can jump straight to the
+/// right `Environment` scope instead of doing a linear search.
+pub fn resolve(ast: &mut Ast) -> Vec<RoxError> {
+    let mut resolver = Resolver::new();
+    resolver.resolve_stmts(ast);
+    resolver.errors
+}
+
+struct Resolver {
+    // each scope maps a name to whether it has finished being defined yet.
+    scopes: Vec<HashMap<String, bool>>,
+    errors: Vec<RoxError>,
+}
+
+impl Resolver {
+    fn new() -> Self {
+        Self {
+            scopes: vec![],
+            errors: vec![],
+        }
+    }
+
+    fn resolve_stmts(&mut self, stmts: &mut [Stmt]) {
+        for stmt in stmts {
+            self.resolve_stmt(stmt);
+        }
+    }
+
+    fn resolve_stmt(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Block(stmts) => {
+                self.begin_scope();
+                self.resolve_stmts(stmts);
+                self.end_scope();
+            }
+            Stmt::Expr(expr) => self.resolve_expr(expr),
+            Stmt::If(cond, then_stmt, else_stmt) => {
+                self.resolve_expr(cond);
+                self.resolve_stmt(then_stmt);
+                if let Some(else_stmt) = else_stmt {
+                    self.resolve_stmt(else_stmt);
+                }
+            }
+            Stmt::While(cond, body) => {
+                self.resolve_expr(cond);
+                self.resolve_stmt(body);
+            }
+            Stmt::For(cond, increment, body) => {
+                self.resolve_expr(cond);
+                if let Some(increment) = increment {
+                    self.resolve_expr(increment);
+                }
+                self.resolve_stmt(body);
+            }
+            Stmt::Break | Stmt::Continue => {}
+            Stmt::Let(name, initializer) => {
+                self.declare(name);
+                if let Some(expr) = initializer {
+                    self.resolve_expr(expr);
+                }
+                self.define(name);
+            }
+            Stmt::Print(expr) => self.resolve_expr(expr),
+            Stmt::Function(name, params, body) => {
+                self.declare(name);
+                self.define(name);
+                self.resolve_function(params, body);
+            }
+            Stmt::Return(expr) => {
+                if let Some(expr) = expr {
+                    self.resolve_expr(expr);
+                }
+            }
+        }
+    }
+
+    // params share the function's outer scope; `body` (a `Block`) begins its
+    // own nested scope, same as the interpreter's `call_function`/`block`.
+    fn resolve_function(&mut self, params: &Vec<String>, body: &mut Stmt) {
+        self.begin_scope();
+        for param in params {
+            self.declare(param);
+            self.define(param);
+        }
+        self.resolve_stmt(body);
+        self.end_scope();
+    }
+
+    fn resolve_expr(&mut self, expr: &mut Expr) {
+        let span = expr.span;
+        match &mut expr.kind {
+            ExprKind::Binary(l, _, r) | ExprKind::Logical(l, _, r) => {
+                self.resolve_expr(l);
+                self.resolve_expr(r);
+            }
+            ExprKind::Unary(_, inner) => self.resolve_expr(inner),
+            ExprKind::Call(callee, args) => {
+                self.resolve_expr(callee);
+                for arg in args {
+                    self.resolve_expr(arg);
+                }
+            }
+            ExprKind::Number(_) | ExprKind::Boolean(_) | ExprKind::Nil | ExprKind::String(_) => {}
+            ExprKind::Variable(name, depth) => {
+                if let Some(scope) = self.scopes.last() {
+                    if scope.get(name) == Some(&false) {
+                        self.errors.push(RoxError::resolve(
+                            span,
+                            format!(
+                                "cannot read local variable '{}' in its own initializer.",
+                                name
+                            ),
+                        ));
+                    }
+                }
+                *depth = self.resolve_local(name);
+            }
+            ExprKind::Assignment(name, inner, depth) => {
+                self.resolve_expr(inner);
+                *depth = self.resolve_local(name);
+            }
+        }
+    }
+
+    // distance, in scopes, from the innermost scope to the one declaring `name`.
+    fn resolve_local(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains_key(name))
+    }
+
+    fn declare(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), false);
+        }
+    }
+
+    fn define(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), true);
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+}
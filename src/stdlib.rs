@@ -0,0 +1,68 @@
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::environment::Environment;
+use crate::value::Value;
+
+/// Registers the native functions rox programs can call into: `clock()`,
+/// `len(s)`, `str(x)`, `num(s)`, and `input()`.
+pub fn load(env: &mut Environment) {
+    define_native(env, "clock", 0, clock);
+    define_native(env, "len", 1, len);
+    define_native(env, "str", 1, str_);
+    define_native(env, "num", 1, num);
+    define_native(env, "input", 0, input);
+}
+
+fn define_native(
+    env: &mut Environment,
+    name: &str,
+    arity: usize,
+    func: fn(Vec<Value>) -> Result<Value, String>,
+) {
+    env.define_var(
+        name.to_string(),
+        Some(Value::NativeFn(name.to_string(), arity, func)),
+    );
+}
+
+fn clock(_args: Vec<Value>) -> Result<Value, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?;
+    Ok(Value::Num(now.as_secs_f64()))
+}
+
+fn len(args: Vec<Value>) -> Result<Value, String> {
+    match &args[0] {
+        Value::String(s) => Ok(Value::Num(s.chars().count() as f64)),
+        _ => Err("len() expects a string argument.".to_string()),
+    }
+}
+
+fn str_(args: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::String(args[0].display()))
+}
+
+fn num(args: Vec<Value>) -> Result<Value, String> {
+    match &args[0] {
+        Value::Num(n) => Ok(Value::Num(*n)),
+        Value::String(s) => s
+            .trim()
+            .parse::<f64>()
+            .map(Value::Num)
+            .map_err(|_| format!("Cannot convert '{}' to a number.", s)),
+        _ => Err("num() expects a string or number argument.".to_string()),
+    }
+}
+
+fn input(_args: Vec<Value>) -> Result<Value, String> {
+    std::io::stdout().flush().map_err(|e| e.to_string())?;
+
+    let mut line = String::new();
+    std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| e.to_string())?;
+
+    Ok(Value::String(line.trim_end_matches(['\n', '\r']).to_string()))
+}
@@ -14,6 +14,10 @@ pub enum Token {
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
     // One or two character tokens.
     Not,
     NotEqual,
@@ -26,9 +30,11 @@ pub enum Token {
     // Literals.
     Identifier,
     String,
-    Number,
+    Number(f64),
     // Keywords.
     And,
+    Break,
+    Continue,
     Else,
     False,
     For,
@@ -41,31 +47,52 @@ pub enum Token {
     Return,
     True,
     Let,
+    While,
 
-    Error,
     Eof,
 }
 
+/// A human-readable location in the source: 1-based line/column alongside
+/// the raw char offset, maintained by the `Lexer` as it scans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: u32,
+    pub column: u32,
+    pub offset: u32,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct WithSpan<T> {
     value: T,
-    start_pos: u32,
-    end_pos: u32,
+    start: Position,
+    end: Position,
 }
 
 impl<T> WithSpan<T>
 where
     T: Copy + Clone,
 {
-    pub fn new(value: T, start_pos: u32, end_pos: u32) -> Self {
-        Self {
-            value,
-            start_pos,
-            end_pos,
-        }
+    pub fn new(value: T, start: Position, end: Position) -> Self {
+        Self { value, start, end }
     }
 
     pub fn value(&self) -> T {
         self.value
     }
+
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    pub fn end(&self) -> Position {
+        self.end
+    }
+
+    pub fn start_pos(&self) -> u32 {
+        self.start.offset
+    }
+
+    pub fn end_pos(&self) -> u32 {
+        self.end.offset
+    }
 }
@@ -1,20 +1,78 @@
-use std::ops::{Add, Div, Mul, Not, Sub};
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Not, Rem, Sub};
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
+use crate::ast::Stmt;
+use crate::environment::Environment;
+
+#[derive(Debug, Clone)]
 pub enum Value {
     Bool(bool),
     Num(f64),
     Nil,
     String(String),
+    // Function(params, body, closure)
+    Function(Vec<String>, Box<Stmt>, Environment),
+    // NativeFn(name, arity, implementation)
+    NativeFn(String, usize, fn(Vec<Value>) -> Result<Value, String>),
+}
+
+impl Value {
+    // Lox truthiness: `nil` and `false` are falsey, everything else truthy.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+
+    // how a value renders when stringified, e.g. by `+` concatenation or `str()`.
+    pub fn display(&self) -> String {
+        match self {
+            Value::Bool(b) => b.to_string(),
+            Value::Num(n) => n.to_string(),
+            Value::Nil => "nil".to_string(),
+            Value::String(s) => s.clone(),
+            Value::Function(..) => "<fn>".to_string(),
+            Value::NativeFn(name, ..) => format!("<native fn {}>", name),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Num(a), Value::Num(b)) => a == b,
+            (Value::Nil, Value::Nil) => true,
+            (Value::String(a), Value::String(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => a.partial_cmp(b),
+            (Value::Num(a), Value::Num(b)) => a.partial_cmp(b),
+            (Value::String(a), Value::String(b)) => a.partial_cmp(b),
+            (Value::Nil, Value::Nil) => Some(std::cmp::Ordering::Equal),
+            _ => None,
+        }
+    }
 }
 
 impl Not for Value {
     type Output = Result<Value, String>;
 
     fn not(self) -> Self::Output {
+        Ok(Value::Bool(!self.is_truthy()))
+    }
+}
+
+impl Neg for Value {
+    type Output = Result<Value, String>;
+
+    fn neg(self) -> Self::Output {
         match self {
-            Value::Bool(b) => Ok(Value::Bool(!b)),
-            _ => Err("oprands must be boolean".to_string()),
+            Value::Num(n) => Ok(Value::Num(-n)),
+            _ => Err("Operand must be a number.".to_string()),
         }
     }
 }
@@ -65,19 +123,63 @@ impl Add for Value {
     type Output = Result<Value, String>;
 
     fn add(self, rhs: Self) -> Self::Output {
+        if let (Value::Num(a), Value::Num(b)) = (&self, &rhs) {
+            return Ok(Value::Num(a + b));
+        }
+
+        // concatenate if either side is a string, stringifying the other operand.
+        if matches!(self, Value::String(_)) || matches!(rhs, Value::String(_)) {
+            return Ok(Value::String(self.display() + &rhs.display()));
+        }
+
+        Err("both oprands must be number or string type.".to_string())
+    }
+}
+
+impl Rem for Value {
+    type Output = Result<Value, String>;
+
+    fn rem(self, rhs: Self) -> Self::Output {
         if let Value::Num(a) = self {
             if let Value::Num(b) = rhs {
-                return Ok(Value::Num(a + b));
+                return Ok(Value::Num(a % b));
             }
         }
 
-        if let Value::String(mut a) = self {
-            if let Value::String(b) = rhs {
-                a.push_str(&b);
-                return Ok(Value::String(a));
-            }
-        }
+        return Err("Both operands must be of number type.".to_string());
+    }
+}
+
+// bitwise operators only make sense on whole numbers, so `Value::Num`s with a
+// fractional part are rejected rather than silently truncated.
+fn as_integer(value: &Value) -> Result<i64, String> {
+    match value {
+        Value::Num(n) if n.fract() == 0.0 => Ok(*n as i64),
+        Value::Num(_) => Err("Bitwise operands must be whole numbers.".to_string()),
+        _ => Err("Bitwise operands must be numbers.".to_string()),
+    }
+}
+
+impl BitAnd for Value {
+    type Output = Result<Value, String>;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Ok(Value::Num((as_integer(&self)? & as_integer(&rhs)?) as f64))
+    }
+}
+
+impl BitOr for Value {
+    type Output = Result<Value, String>;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Ok(Value::Num((as_integer(&self)? | as_integer(&rhs)?) as f64))
+    }
+}
+
+impl BitXor for Value {
+    type Output = Result<Value, String>;
 
-        return Err("both oprands must be number or string type.".to_string());
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        Ok(Value::Num((as_integer(&self)? ^ as_integer(&rhs)?) as f64))
     }
 }